@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fmt::Debug,
     fs::{self, File},
     hash::Hash,
@@ -57,7 +57,8 @@ impl Command {
     /// element" for that database.
     ///
     /// The discrepancies and extra elements, along with a brief summary of the diff results are
-    /// then written to a file in the output directory.
+    /// then written to a file in the output directory. For tables with differences, a
+    /// unified-diff-like rendering of them is written to a `.diff` file next to it.
     pub fn execute(self, tool: &DbTool<'_, DatabaseEnvRO>) -> eyre::Result<()> {
         // open second db
         let second_db_path: PathBuf = self.secondary_datadir.join("db").into();
@@ -226,6 +227,13 @@ where
 
     let full_file_name = output_dir.as_ref().join(file_name);
     info!("Done writing diff results for {table_name} to {}", full_file_name.display());
+
+    if discrepancies > 0 || extra_elements > 0 {
+        let diff_file_name = output_dir.as_ref().join(format!("{table_name}.diff"));
+        fs::write(&diff_file_name, result.render())?;
+        info!("Wrote unified diff for {table_name} to {}", diff_file_name.display());
+    }
+
     Ok(())
 }
 
@@ -300,11 +308,9 @@ struct TableDiffElement<T: Table> {
     key: T::Key,
 
     /// The element from the first table
-    #[allow(dead_code)]
     first: T::Value,
 
     /// The element from the second table
-    #[allow(dead_code)]
     second: T::Value,
 }
 
@@ -344,6 +350,31 @@ where
     fn push_extra_element(&mut self, element: ExtraTableElement<T>) {
         self.extra_elements.insert(element.key().clone(), element);
     }
+
+    /// Renders the result as a unified-diff-like text, ordered by key.
+    ///
+    /// Rows only present in the first database are prefixed with `-`, rows only present in the
+    /// second database with `+`, and rows with different values show both versions.
+    fn render(&self) -> String {
+        let mut lines = BTreeMap::new();
+        for (key, element) in &self.discrepancies {
+            lines.insert(
+                key,
+                format!("-{key:?}: {:?}\n+{key:?}: {:?}\n", element.first, element.second),
+            );
+        }
+        for (key, element) in &self.extra_elements {
+            let line = match element {
+                ExtraTableElement::First { value, .. } => format!("-{key:?}: {value:?}\n"),
+                ExtraTableElement::Second { value, .. } => format!("+{key:?}: {value:?}\n"),
+            };
+            lines.insert(key, line);
+        }
+
+        let mut rendered = format!("--- first/{0}\n+++ second/{0}\n", T::NAME);
+        rendered.extend(lines.into_values());
+        rendered
+    }
 }
 
 impl<T> TableDiffResult<T>
@@ -391,11 +422,9 @@ where
 #[derive(Debug)]
 enum ExtraTableElement<T: Table> {
     /// The extra element that is in the first table
-    #[allow(dead_code)]
     First { key: T::Key, value: T::Value },
 
     /// The extra element that is in the second table
-    #[allow(dead_code)]
     Second { key: T::Key, value: T::Value },
 }
 
@@ -408,3 +437,23 @@ impl<T: Table> ExtraTableElement<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_table_diff() {
+        let mut result = TableDiffResult::<TransactionBlock>::default();
+        result.try_push_discrepancy(3, None, Some(30));
+        result.try_push_discrepancy(1, Some(10), Some(11));
+        result.try_push_discrepancy(2, Some(20), None);
+        // equal values are not a discrepancy
+        result.try_push_discrepancy(4, Some(40), Some(40));
+
+        assert_eq!(
+            result.render(),
+            "--- first/TransactionBlock\n+++ second/TransactionBlock\n-1: 10\n+1: 11\n-2: 20\n+3: 30\n"
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use crate::utils::DbTool;
+use crate::utils::{parse_table_key, DbTool};
 use clap::Parser;
 
 use reth_db::{database::Database, table::Table, RawKey, RawTable, TableType, TableViewer, Tables};
@@ -13,7 +13,10 @@ pub struct Command {
     pub table: Tables,
 
     /// The key to get content for
-    #[arg(value_parser = maybe_json_value_parser)]
+    ///
+    /// Either the JSON representation of the key, a decimal or `0x` prefixed hex number for
+    /// tables keyed by number, a `0x` prefixed hash or address, or `latest` for the last key in
+    /// the table.
     pub key: String,
 
     /// Output bytes instead of human-readable decoded value
@@ -39,7 +42,7 @@ impl Command {
     pub fn table_key<T: Table>(&self) -> Result<T::Key, eyre::Error> {
         assert_eq!(T::NAME, self.table.name());
 
-        serde_json::from_str::<T::Key>(&self.key).map_err(|e| eyre::eyre!(e))
+        parse_table_key::<T>(&self.key)
     }
 }
 
@@ -53,7 +56,11 @@ impl<DB: Database> TableViewer<()> for GetValueViewer<'_, DB> {
 
     fn view<T: Table>(&self) -> Result<(), Self::Error> {
         // get a key for given table
-        let key = self.args.table_key::<T>()?;
+        let key = if self.args.key == "latest" {
+            self.tool.latest_key::<T>()?
+        } else {
+            self.args.table_key::<T>()?
+        };
 
         let content = if self.args.raw {
             self.tool
@@ -76,15 +83,6 @@ impl<DB: Database> TableViewer<()> for GetValueViewer<'_, DB> {
     }
 }
 
-/// Map the user input value to json
-fn maybe_json_value_parser(value: &str) -> Result<String, eyre::Error> {
-    if serde_json::from_str::<serde::de::IgnoredAny>(value).is_ok() {
-        Ok(value.to_string())
-    } else {
-        serde_json::to_string(&value).map_err(|e| eyre::eyre!(e))
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -108,6 +106,9 @@ mod tests {
         let args = CommandParser::<Command>::parse_from(["reth", "Headers", "123"]).args;
         assert_eq!(args.table_key::<Headers>().unwrap(), 123);
 
+        let args = CommandParser::<Command>::parse_from(["reth", "Headers", "0x7b"]).args;
+        assert_eq!(args.table_key::<Headers>().unwrap(), 123);
+
         let args = CommandParser::<Command>::parse_from([
             "reth",
             "HashedAccount",
@@ -1,5 +1,5 @@
 use super::tui::DbListTUI;
-use crate::utils::{DbTool, ListFilter};
+use crate::utils::{DbTool, ListFilter, RowError};
 use clap::Parser;
 use eyre::WrapErr;
 use reth_db::{database::Database, table::Table, DatabaseEnvRO, RawValue, TableViewer, Tables};
@@ -46,36 +46,40 @@ pub struct Command {
     /// Output bytes instead of human-readable decoded value
     #[arg(long)]
     raw: bool,
+    /// Report rows that can't be read or decoded and continue, instead of failing on the first
+    /// one. Only supported together with `--json` or `--count`.
+    #[arg(long)]
+    report_errors: bool,
 }
 
 impl Command {
     /// Execute `db list` command
     pub fn execute(self, tool: &DbTool<'_, DatabaseEnvRO>) -> eyre::Result<()> {
+        if self.report_errors && !(self.json || self.count) {
+            eyre::bail!("`--report-errors` can only be used together with `--json` or `--count`")
+        }
+
         self.table.view(&ListTableViewer { tool, args: &self })
     }
 
     /// Generate [`ListFilter`] from command.
-    pub fn list_filter(&self) -> ListFilter {
-        let search = self
-            .search
-            .as_ref()
-            .map(|search| {
-                if let Some(search) = search.strip_prefix("0x") {
-                    return hex::decode(search).unwrap()
-                }
-                search.as_bytes().to_vec()
-            })
-            .unwrap_or_default();
-
-        ListFilter {
+    pub fn list_filter(&self) -> eyre::Result<ListFilter> {
+        let filter = ListFilter {
             skip: self.skip,
             len: self.len,
-            search,
+            search: Vec::new(),
             min_row_size: self.min_row_size,
             min_key_size: self.min_key_size,
             min_value_size: self.min_value_size,
             reverse: self.reverse,
             only_count: self.count,
+            report_errors: self.report_errors,
+        };
+
+        match &self.search {
+            Some(search) if search.starts_with("0x") => filter.with_search_hex(search),
+            Some(search) => Ok(ListFilter { search: search.as_bytes().to_vec(), ..filter }),
+            None => Ok(filter),
         }
     }
 }
@@ -105,10 +109,14 @@ impl TableViewer<()> for ListTableViewer<'_> {
             }
 
 
-            let list_filter = self.args.list_filter();
+            let list_filter = self.args.list_filter()?;
 
             if self.args.json || self.args.count {
-                let (list, count) = self.tool.list::<T>(&list_filter)?;
+                let (list, errors, count) = self.tool.list::<T>(&list_filter)?;
+
+                for RowError { key, error } in errors {
+                    error!(target: "reth::cli", key = hex::encode(key), %error, "Failed to read row");
+                }
 
                 if self.args.count {
                     println!("{count} entries found.")
@@ -68,7 +68,12 @@ pub struct Command {
 /// `reth db` subcommands
 pub enum Subcommands {
     /// Lists all the tables, their entry count and their size
-    Stats,
+    Stats {
+        /// Only show tables whose name matches this glob pattern (e.g. `Account*`).
+        ///
+        /// Supports `*` and `?` wildcards and is matched case-insensitively.
+        pattern: Option<String>,
+    },
     /// Lists the contents of a table
     List(list::Command),
     /// Create a diff between two database tables or two entire databases.
@@ -100,7 +105,7 @@ impl Command {
 
         match self.command {
             // TODO: We'll need to add this on the DB trait.
-            Subcommands::Stats { .. } => {
+            Subcommands::Stats { pattern } => {
                 let db = open_db_read_only(&db_path, self.db.log_level)?;
                 let tool = DbTool::new(&db, self.chain.clone())?;
                 let mut stats_table = ComfyTable::new();
@@ -115,9 +120,20 @@ impl Command {
                 ]);
 
                 tool.db.view(|tx| {
-                    let mut tables =
-                        Tables::ALL.iter().map(|table| table.name()).collect::<Vec<_>>();
-                    tables.sort();
+                    let tables = Tables::ALL
+                        .iter()
+                        .map(|table| table.name())
+                        .filter(|table| {
+                            pattern.as_ref().map_or(true, |pattern| glob_matches(pattern, table))
+                        })
+                        .collect::<Vec<_>>();
+                    if tables.is_empty() {
+                        eyre::bail!(
+                            "No table matches the pattern {:?}",
+                            pattern.as_deref().unwrap_or_default()
+                        )
+                    }
+                    let mut rows = Vec::with_capacity(tables.len());
                     let mut total_size = 0;
                     for table in tables {
                         let table_db =
@@ -146,6 +162,14 @@ impl Command {
                             .add_cell(Cell::new(leaf_pages))
                             .add_cell(Cell::new(overflow_pages))
                             .add_cell(Cell::new(human_bytes(table_size as f64)));
+                        rows.push((table_size, table, row));
+                    }
+
+                    // Largest tables first, ties broken by name
+                    rows.sort_by(|(a_size, a_name, _), (b_size, b_name, _)| {
+                        b_size.cmp(a_size).then_with(|| a_name.cmp(b_name))
+                    });
+                    for (_, _, row) in rows {
                         stats_table.add_row(row);
                     }
 
@@ -158,7 +182,12 @@ impl Command {
                     stats_table.add_row(seperator);
 
                     let mut row = Row::new();
-                    row.add_cell(Cell::new("Total DB size"))
+                    let total_label = if pattern.is_some() {
+                        "Total size of matched tables"
+                    } else {
+                        "Total DB size"
+                    };
+                    row.add_cell(Cell::new(total_label))
                         .add_cell(Cell::new(""))
                         .add_cell(Cell::new(""))
                         .add_cell(Cell::new(""))
@@ -250,11 +279,64 @@ impl Command {
     }
 }
 
+/// Returns `true` if `name` matches the glob `pattern`, ignoring ASCII case.
+///
+/// `*` matches any sequence of characters (including none) and `?` matches exactly one.
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.as_bytes();
+    let name = name.as_bytes();
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` in the pattern and the name position it was tried at
+    let mut backtrack = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(c) if *c == b'?' || c.eq_ignore_ascii_case(&name[n]) => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    n = matched + 1;
+                    backtrack = Some((star, n));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == b'*')
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::path::Path;
 
+    #[test]
+    fn parse_stats_pattern() {
+        let cmd = Command::try_parse_from(["reth", "stats", "Account*"]).unwrap();
+        assert!(matches!(cmd.command, Subcommands::Stats { pattern: Some(p) } if p == "Account*"));
+    }
+
+    #[test]
+    fn glob() {
+        assert!(glob_matches("Account*", "AccountHistory"));
+        assert!(glob_matches("account*", "AccountChangeSet"));
+        assert!(glob_matches("*State", "PlainStorageState"));
+        assert!(glob_matches("*Storage*", "HashedStorage"));
+        assert!(glob_matches("Header?", "Headers"));
+        assert!(glob_matches("*", "Bytecodes"));
+        assert!(!glob_matches("Account*", "PlainAccountState"));
+        assert!(!glob_matches("Header?", "HeaderNumbers"));
+        assert!(!glob_matches("", "Headers"));
+    }
+
     #[test]
     fn parse_stats_globals() {
         let path = format!("../{}", SUPPORTED_CHAINS[0]);
@@ -9,7 +9,7 @@ use crate::{
     init::init_genesis,
     node::events,
     runner::CliContext,
-    utils::get_single_header,
+    utils::get_single_header_untimed,
 };
 use clap::Parser;
 use futures::{stream::select as stream_select, StreamExt};
@@ -184,7 +184,7 @@ impl Command {
     ) -> eyre::Result<B256> {
         info!(target: "reth::cli", ?block, "Fetching block from the network.");
         loop {
-            match get_single_header(&client, BlockHashOrNumber::Number(block)).await {
+            match get_single_header_untimed(&client, BlockHashOrNumber::Number(block)).await {
                 Ok(tip_header) => {
                     info!(target: "reth::cli", ?block, "Successfully fetched block");
                     return Ok(tip_header.hash)
@@ -18,7 +18,7 @@ use crate::{
     node::cl_events::ConsensusLayerHealthEvents,
     prometheus_exporter,
     runner::CliContext,
-    utils::get_single_header,
+    utils::get_single_header_untimed,
     version::SHORT_VERSION,
 };
 use clap::{value_parser, Parser};
@@ -793,7 +793,7 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
 
         info!(target: "reth::cli", ?tip, "Fetching tip block from the network.");
         loop {
-            match get_single_header(&client, tip).await {
+            match get_single_header_untimed(&client, tip).await {
                 Ok(tip_header) => {
                     info!(target: "reth::cli", ?tip, "Successfully fetched tip");
                     return Ok(tip_header)
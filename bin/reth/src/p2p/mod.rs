@@ -6,17 +6,27 @@ use crate::{
         DatabaseArgs, DiscoveryArgs,
     },
     dirs::{DataDirPath, MaybePlatformPath},
-    utils::get_single_header,
+    utils::{
+        get_single_body_from_peer, get_single_body_with_timeout, get_single_header,
+        get_single_header_from_peer, wait_for_session, DEFAULT_REQUEST_TIMEOUT,
+    },
 };
 use backon::{ConstantBuilder, Retryable};
 use clap::{Parser, Subcommand};
+use eyre::WrapErr;
 use reth_config::Config;
+use reth_consensus_common::validation::validate_block_standalone;
 use reth_db::open_db;
 use reth_discv4::NatResolver;
-use reth_interfaces::p2p::bodies::client::BodiesClient;
-use reth_primitives::{BlockHashOrNumber, ChainSpec, NodeRecord};
+use reth_network::{FetchClient, NetworkHandle};
+use reth_primitives::{
+    BlockHashOrNumber, ChainSpec, NodeRecord, PeerId, SealedBlock, SealedHeader,
+};
 use reth_provider::ProviderFactory;
-use std::{path::PathBuf, sync::Arc};
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+/// How long `--pin-peer` waits for a session with the trusted peer before giving up.
+const PEER_SESSION_TIMEOUT: Duration = Duration::from_secs(60);
 
 /// `reth p2p` command
 #[derive(Debug, Parser)]
@@ -69,6 +79,12 @@ pub struct Command {
     #[arg(long, default_value = "5")]
     retries: usize,
 
+    /// Send all requests to the `--trusted-peer` instead of letting the fetch client pick one.
+    ///
+    /// Requests are only sent once a session with the peer is established.
+    #[arg(long, requires = "trusted_peer")]
+    pin_peer: bool,
+
     #[arg(long, default_value = "any")]
     nat: NatResolver,
 
@@ -93,6 +109,11 @@ pub enum Subcommands {
         /// The block number or hash
         #[arg(value_parser = hash_or_num_value_parser)]
         id: BlockHashOrNumber,
+
+        /// Print the block without running standalone validation on it, e.g. to inspect a body
+        /// that fails validation.
+        #[arg(long)]
+        skip_validation: bool,
     },
 }
 impl Command {
@@ -135,48 +156,90 @@ impl Command {
         let retries = self.retries.max(1);
         let backoff = ConstantBuilder::default().with_max_times(retries);
 
+        let peer_id = self.trusted_peer.filter(|_| self.pin_peer).map(|peer| peer.id);
+        if let Some(peer_id) = peer_id {
+            println!("Waiting for a session with {peer_id}...");
+            wait_for_session(&network, peer_id, PEER_SESSION_TIMEOUT).await?;
+        }
+        let from = peer_id.map(|peer_id| format!(" from {peer_id}")).unwrap_or_default();
+
+        let (network, fetch_client) = (&network, &fetch_client);
+        let header = |id| {
+            (move || fetch_header(network, fetch_client, peer_id, id))
+                .retry(&backoff)
+                .notify(|err, _| println!("Error requesting header: {err}. Retrying..."))
+        };
+
         match self.command {
             Subcommands::Header { id } => {
-                let header = (move || get_single_header(fetch_client.clone(), id))
-                    .retry(&backoff)
-                    .notify(|err, _| println!("Error requesting header: {err}. Retrying..."))
-                    .await?;
-                println!("Successfully downloaded header: {header:?}");
+                let header = header(id).await?;
+                println!("Successfully downloaded header{from}: {header:?}");
             }
-            Subcommands::Body { id } => {
-                let hash = match id {
-                    BlockHashOrNumber::Hash(hash) => hash,
-                    BlockHashOrNumber::Number(number) => {
-                        println!("Block number provided. Downloading header first...");
-                        let client = fetch_client.clone();
-                        let header = (move || {
-                            get_single_header(client.clone(), BlockHashOrNumber::Number(number))
-                        })
-                        .retry(&backoff)
-                        .notify(|err, _| println!("Error requesting header: {err}. Retrying..."))
-                        .await?;
-                        header.hash()
-                    }
-                };
-                let (_, result) = (move || {
-                    let client = fetch_client.clone();
-                    client.get_block_bodies(vec![hash])
+            Subcommands::Body { id, skip_validation } => {
+                if let BlockHashOrNumber::Number(_) = id {
+                    println!("Block number provided. Downloading header first...");
+                }
+                let header = header(id).await?;
+                // Validation errors are not retried, the peer already sent the body it has.
+                let block = (|| {
+                    fetch_body(network, fetch_client, peer_id, self.chain.clone(), header.clone())
                 })
                 .retry(&backoff)
                 .notify(|err, _| println!("Error requesting block: {err}. Retrying..."))
-                .await?
-                .split();
-                if result.len() != 1 {
-                    eyre::bail!(
-                        "Invalid number of headers received. Expected: 1. Received: {}",
-                        result.len()
-                    )
+                .await?;
+                if !skip_validation {
+                    validate_block_standalone(&block, &self.chain).wrap_err_with(|| {
+                        format!(
+                            "Downloaded invalid body for block {}, use --skip-validation to \
+                             print it anyway",
+                            block.number
+                        )
+                    })?;
                 }
-                let body = result.into_iter().next().unwrap();
-                println!("Successfully downloaded body: {body:?}")
+                println!("Successfully downloaded body{from}: {block:?}")
             }
         }
 
         Ok(())
     }
 }
+
+/// Fetches a single header from the given peer, or from any peer if `peer_id` is `None`.
+async fn fetch_header(
+    network: &NetworkHandle,
+    fetch_client: &FetchClient,
+    peer_id: Option<PeerId>,
+    id: BlockHashOrNumber,
+) -> eyre::Result<SealedHeader> {
+    match peer_id {
+        Some(peer_id) => get_single_header_from_peer(network, peer_id, id).await,
+        None => get_single_header(fetch_client.clone(), id).await,
+    }
+}
+
+/// Fetches the body for `header` from the given peer, or from any peer if `peer_id` is `None`.
+///
+/// The block is not validated.
+async fn fetch_body(
+    network: &NetworkHandle,
+    fetch_client: &FetchClient,
+    peer_id: Option<PeerId>,
+    chain_spec: Arc<ChainSpec>,
+    header: SealedHeader,
+) -> eyre::Result<SealedBlock> {
+    match peer_id {
+        Some(peer_id) => {
+            get_single_body_from_peer(network, peer_id, chain_spec, header, true).await
+        }
+        None => {
+            get_single_body_with_timeout(
+                fetch_client.clone(),
+                chain_spec,
+                header,
+                DEFAULT_REQUEST_TIMEOUT,
+                true,
+            )
+            .await
+        }
+    }
+}
@@ -2,28 +2,45 @@
 
 use boyer_moore_magiclen::BMByte;
 use eyre::Result;
+use futures::StreamExt;
 use reth_consensus_common::validation::validate_block_standalone;
 use reth_db::{
-    cursor::DbCursorRO,
+    cursor::{DbCursorRO, DbDupCursorRO},
     database::Database,
+    models::ShardedKey,
     table::{Decode, Decompress, Table, TableRow},
     transaction::{DbTx, DbTxMut},
-    DatabaseError, RawTable, TableRawRow,
+    AccountChangeSet, AccountHistory, BlockBodyIndices, BlockOmmers, BlockWithdrawals, Bytecodes,
+    CanonicalHeaders, DatabaseError, HeaderNumbers, Headers, PlainAccountState, PlainStorageState,
+    RawTable, SyncStage, TableRawRow, Tables, Transactions, TxHashNumber,
 };
+use reth_eth_wire::{GetBlockBodies, GetBlockHeaders};
 use reth_interfaces::p2p::{
     bodies::client::BodiesClient,
+    error::RequestResult,
     headers::client::{HeadersClient, HeadersRequest},
     priority::Priority,
 };
+use reth_network::{NetworkEvent, NetworkEvents, NetworkHandle, PeerRequest};
+use reth_network_api::{Peers, ReputationChangeKind};
 use reth_primitives::{
-    fs, BlockHashOrNumber, ChainSpec, HeadersDirection, SealedBlock, SealedHeader,
+    fs, hex, stage::StageId, Account, Address, BlockHashOrNumber, BlockNumber, ChainSpec, Header,
+    HeadersDirection, PeerId, SealedBlock, SealedHeader, B256, KECCAK_EMPTY, U256,
 };
+use reth_provider::{BlockReader, ProviderFactory};
+use reth_revm::interpreter::{opcode, OpCode};
 use std::{
+    collections::{HashMap, HashSet},
     env::VarError,
+    fmt::Write as _,
+    io,
+    marker::PhantomData,
     path::{Path, PathBuf},
     rc::Rc,
     sync::Arc,
+    time::Duration,
 };
+use tokio::sync::oneshot;
 use tracing::info;
 
 /// Exposing `open_db_read_only` function
@@ -31,11 +48,50 @@ pub mod db {
     pub use reth_db::open_db_read_only;
 }
 
+/// The default timeout applied to single header and body requests.
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
 /// Get a single header from network
+///
+/// Fails with a timeout error if no response is received within [`DEFAULT_REQUEST_TIMEOUT`]. See
+/// [`get_single_header_with_timeout`].
 pub async fn get_single_header<Client>(
     client: Client,
     id: BlockHashOrNumber,
 ) -> Result<SealedHeader>
+where
+    Client: HeadersClient,
+{
+    get_single_header_with_timeout(client, id, DEFAULT_REQUEST_TIMEOUT).await
+}
+
+/// Get a single header from network, failing if no response is received within `timeout`.
+///
+/// The peer that answered with an invalid response is reported. On timeout no peer is reported,
+/// since the client doesn't expose which peer the request was sent to. Use
+/// [`get_single_header_from_peer`] to have an unresponsive peer reported.
+///
+/// The timeout doesn't cancel the request inside the client: while no peer is connected it stays
+/// queued and is sent once one connects. Use [`get_single_header_untimed`] where waiting for peers
+/// is expected.
+pub async fn get_single_header_with_timeout<Client>(
+    client: Client,
+    id: BlockHashOrNumber,
+    timeout: Duration,
+) -> Result<SealedHeader>
+where
+    Client: HeadersClient,
+{
+    tokio::time::timeout(timeout, get_single_header_untimed(client, id))
+        .await
+        .map_err(|_| eyre::eyre!("Timed out after {timeout:?} requesting header {id:?}"))?
+}
+
+/// Get a single header from network, waiting for as long as it takes a peer to respond.
+pub async fn get_single_header_untimed<Client>(
+    client: Client,
+    id: BlockHashOrNumber,
+) -> Result<SealedHeader>
 where
     Client: HeadersClient,
 {
@@ -44,8 +100,94 @@ where
     let (peer_id, response) =
         client.get_headers_with_priority(request, Priority::High).await?.split();
 
-    if response.len() != 1 {
+    ensure_single_header(response, id).map_err(|err| {
         client.report_bad_message(peer_id);
+        err
+    })
+}
+
+/// Get a single header from the given peer, bypassing the peer selection of the fetch client.
+///
+/// Returns an error if the peer is not connected or can't serve the requested header.
+pub async fn get_single_header_from_peer(
+    network: &NetworkHandle,
+    peer_id: PeerId,
+    id: BlockHashOrNumber,
+) -> Result<SealedHeader> {
+    let (tx, rx) = oneshot::channel();
+    network.send_request(
+        peer_id,
+        PeerRequest::GetBlockHeaders {
+            request: GetBlockHeaders {
+                start_block: id,
+                limit: 1,
+                skip: 0,
+                direction: HeadersDirection::Rising,
+            },
+            response: tx,
+        },
+    );
+
+    let response = await_peer_response(network, peer_id, rx).await?;
+
+    ensure_single_header(response.0, id).map_err(|err| {
+        network.reputation_change(peer_id, ReputationChangeKind::BadMessage);
+        err
+    })
+}
+
+/// Waits until a session with `peer_id` is established, failing if it isn't within `timeout`.
+///
+/// Returns immediately if the peer is already connected. Requests sent with
+/// [`get_single_header_from_peer`] or [`get_single_body_from_peer`] before that are dropped.
+pub async fn wait_for_session(
+    network: &NetworkHandle,
+    peer_id: PeerId,
+    timeout: Duration,
+) -> Result<()> {
+    // Subscribe before looking up the active sessions, so the event can't be missed in between.
+    let mut events = network.event_listener();
+    if network.get_peer_by_id(peer_id).await?.is_some() {
+        return Ok(())
+    }
+
+    let established = async {
+        while let Some(event) = events.next().await {
+            if matches!(event, NetworkEvent::SessionEstablished { peer_id: id, .. } if id == peer_id)
+            {
+                return true
+            }
+        }
+        false
+    };
+
+    match tokio::time::timeout(timeout, established).await {
+        Ok(true) => Ok(()),
+        Ok(false) => eyre::bail!("Network stopped before a session with {peer_id} was established"),
+        Err(_) => eyre::bail!("Timed out after {timeout:?} waiting for a session with {peer_id}"),
+    }
+}
+
+/// Waits up to [`DEFAULT_REQUEST_TIMEOUT`] for the response of a request sent to `peer_id`,
+/// reporting the peer if it doesn't respond in time.
+async fn await_peer_response<T>(
+    network: &NetworkHandle,
+    peer_id: PeerId,
+    rx: oneshot::Receiver<RequestResult<T>>,
+) -> Result<T> {
+    match tokio::time::timeout(DEFAULT_REQUEST_TIMEOUT, rx).await {
+        Ok(Ok(response)) => Ok(response?),
+        Ok(Err(_)) => eyre::bail!("Peer {peer_id} is not connected or dropped the request"),
+        Err(_) => {
+            network.reputation_change(peer_id, ReputationChangeKind::Timeout);
+            eyre::bail!("Peer {peer_id} timed out after {DEFAULT_REQUEST_TIMEOUT:?}")
+        }
+    }
+}
+
+/// Checks that the response contains exactly the one requested header and seals it.
+fn ensure_single_header(response: Vec<Header>, id: BlockHashOrNumber) -> Result<SealedHeader> {
+    if response.len() != 1 {
         eyre::bail!("Invalid number of headers received. Expected: 1. Received: {}", response.len())
     }
 
@@ -57,7 +199,6 @@ where
     };
 
     if !valid {
-        client.report_bad_message(peer_id);
         eyre::bail!(
             "Received invalid header. Received: {:?}. Expected: {:?}",
             header.num_hash(),
@@ -68,7 +209,74 @@ where
     Ok(header)
 }
 
+/// An in-process cache for headers fetched with [`get_single_header`].
+///
+/// Repeated lookups of the same header by hash or number within a session are served from the
+/// cache instead of the network.
+#[derive(Debug, Default)]
+pub struct HeaderCache {
+    headers: HashMap<B256, SealedHeader>,
+    numbers: HashMap<BlockNumber, B256>,
+}
+
+impl HeaderCache {
+    /// Returns the header for `id`, fetching and caching it with [`get_single_header`] if it is
+    /// not cached yet.
+    pub async fn get_single_header<Client>(
+        &mut self,
+        client: Client,
+        id: BlockHashOrNumber,
+    ) -> Result<SealedHeader>
+    where
+        Client: HeadersClient,
+    {
+        if let Some(header) = self.get(id) {
+            return Ok(header.clone())
+        }
+
+        let header = get_single_header(client, id).await?;
+        // A header fetched by hash may be on a side fork, so only lookups by number decide which
+        // header a number resolves to.
+        if let BlockHashOrNumber::Number(number) = id {
+            self.numbers.insert(number, header.hash);
+        }
+        self.headers.insert(header.hash, header.clone());
+        Ok(header)
+    }
+
+    /// Returns the cached header for `id`, if any.
+    pub fn get(&self, id: BlockHashOrNumber) -> Option<&SealedHeader> {
+        let hash = match id {
+            BlockHashOrNumber::Hash(hash) => hash,
+            BlockHashOrNumber::Number(number) => *self.numbers.get(&number)?,
+        };
+        self.headers.get(&hash)
+    }
+
+    /// Removes the header for `id` from the cache, so the next lookup fetches it again.
+    pub fn invalidate(&mut self, id: BlockHashOrNumber) {
+        let hash = match id {
+            BlockHashOrNumber::Hash(hash) => Some(hash),
+            BlockHashOrNumber::Number(number) => self.numbers.get(&number).copied(),
+        };
+        if let Some(header) = hash.and_then(|hash| self.headers.remove(&hash)) {
+            if self.numbers.get(&header.number) == Some(&header.hash) {
+                self.numbers.remove(&header.number);
+            }
+        }
+    }
+
+    /// Removes all headers from the cache.
+    pub fn clear(&mut self) {
+        self.headers.clear();
+        self.numbers.clear();
+    }
+}
+
 /// Get a body from network based on header
+///
+/// Fails with a timeout error if no response is received within [`DEFAULT_REQUEST_TIMEOUT`]. See
+/// [`get_single_body_with_timeout`].
 pub async fn get_single_body<Client>(
     client: Client,
     chain_spec: Arc<ChainSpec>,
@@ -77,7 +285,30 @@ pub async fn get_single_body<Client>(
 where
     Client: BodiesClient,
 {
-    let (peer_id, response) = client.get_block_body(header.hash).await?.split();
+    get_single_body_with_timeout(client, chain_spec, header, DEFAULT_REQUEST_TIMEOUT, false).await
+}
+
+/// Get a body from network based on header, failing if no response is received within `timeout`.
+///
+/// As with [`get_single_header_with_timeout`], only a peer that answered with an invalid response
+/// is reported, not one that timed out.
+///
+/// If `skip_validation` is set, the block is returned without running standalone validation on
+/// it, which allows inspecting invalid bodies.
+pub async fn get_single_body_with_timeout<Client>(
+    client: Client,
+    chain_spec: Arc<ChainSpec>,
+    header: SealedHeader,
+    timeout: Duration,
+    skip_validation: bool,
+) -> Result<SealedBlock>
+where
+    Client: BodiesClient,
+{
+    let (peer_id, response) = tokio::time::timeout(timeout, client.get_block_body(header.hash))
+        .await
+        .map_err(|_| eyre::eyre!("Timed out after {timeout:?} requesting body {}", header.hash))??
+        .split();
 
     if response.is_none() {
         client.report_bad_message(peer_id);
@@ -92,21 +323,115 @@ where
         withdrawals: block.withdrawals,
     };
 
-    validate_block_standalone(&block, &chain_spec)?;
+    if !skip_validation {
+        validate_block_standalone(&block, &chain_spec)?;
+    }
+
+    Ok(block)
+}
+
+/// Get a body from the given peer based on header, bypassing the peer selection of the fetch
+/// client.
+///
+/// Returns an error if the peer is not connected or can't serve the requested body. If
+/// `skip_validation` is set, the block is returned without running standalone validation on it.
+pub async fn get_single_body_from_peer(
+    network: &NetworkHandle,
+    peer_id: PeerId,
+    chain_spec: Arc<ChainSpec>,
+    header: SealedHeader,
+    skip_validation: bool,
+) -> Result<SealedBlock> {
+    let (tx, rx) = oneshot::channel();
+    network.send_request(
+        peer_id,
+        PeerRequest::GetBlockBodies { request: GetBlockBodies(vec![header.hash]), response: tx },
+    );
+
+    let response = await_peer_response(network, peer_id, rx).await?;
+
+    if response.0.len() != 1 {
+        network.reputation_change(peer_id, ReputationChangeKind::BadMessage);
+        eyre::bail!(
+            "Invalid number of bodies received. Expected: 1. Received: {}",
+            response.0.len()
+        )
+    }
+
+    let block = response.0.into_iter().next().unwrap();
+    let block = SealedBlock {
+        header,
+        body: block.transactions,
+        ommers: block.ommers,
+        withdrawals: block.withdrawals,
+    };
+
+    if !skip_validation {
+        validate_block_standalone(&block, &chain_spec)?;
+    }
 
     Ok(block)
 }
 
+/// Marker for a [`DbTool`] that exposes all of its methods, including the mutating ones.
+#[derive(Debug)]
+pub struct ReadWrite;
+
+/// Marker for a [`DbTool`] that only exposes methods which don't modify the database or the
+/// filesystem.
+#[derive(Debug)]
+pub struct ReadOnly;
+
 /// Wrapper over DB that implements many useful DB queries.
-pub struct DbTool<'a, DB: Database> {
+pub struct DbTool<'a, DB: Database, Mode = ReadWrite> {
     pub(crate) db: &'a DB,
     pub(crate) chain: Arc<ChainSpec>,
+    /// The tables the reading methods of the tool can access. If `None`, all tables can be
+    /// accessed.
+    allowed_tables: Option<Vec<Tables>>,
+    _mode: PhantomData<Mode>,
 }
 
 impl<'a, DB: Database> DbTool<'a, DB> {
     /// Takes a DB where the tables have already been created.
     pub(crate) fn new(db: &'a DB, chain: Arc<ChainSpec>) -> eyre::Result<Self> {
-        Ok(Self { db, chain })
+        Ok(Self { db, chain, allowed_tables: None, _mode: PhantomData })
+    }
+}
+
+impl<'a, DB: Database> DbTool<'a, DB, ReadOnly> {
+    /// Takes a DB where the tables have already been created and returns a tool that can't
+    /// modify it.
+    ///
+    /// Mutating methods like [`DbTool::drop_table`] are not available on the returned tool. If
+    /// `allowed_tables` is set, every method that reads a table outside of it fails, e.g.
+    /// [`DbTool::get`] for such a table or [`DbTool::account_storage`] if [`PlainStorageState`] is
+    /// not allowed.
+    pub fn new_readonly(
+        db: &'a DB,
+        chain: Arc<ChainSpec>,
+        allowed_tables: Option<Vec<Tables>>,
+    ) -> eyre::Result<Self> {
+        Ok(Self { db, chain, allowed_tables, _mode: PhantomData })
+    }
+}
+
+impl<'a, DB: Database, Mode> DbTool<'a, DB, Mode> {
+    /// Fails if table `T` is not in the allowed tables of this tool.
+    fn ensure_table_allowed<T: Table>(&self) -> Result<()> {
+        self.ensure_tables_allowed(&[T::NAME])
+    }
+
+    /// Fails if any of the tables with the given names is not in the allowed tables of this tool.
+    fn ensure_tables_allowed(&self, names: &[&str]) -> Result<()> {
+        if let Some(allowed_tables) = &self.allowed_tables {
+            for name in names {
+                if !allowed_tables.iter().any(|table| table.name() == *name) {
+                    eyre::bail!("Access to table {name} is not allowed.")
+                }
+            }
+        }
+        Ok(())
     }
 
     /// Grabs the contents of the table within a certain index range and places the
@@ -114,85 +439,349 @@ impl<'a, DB: Database> DbTool<'a, DB> {
     ///
     /// [`ListFilter`] can be used to further
     /// filter down the desired results. (eg. List only rows which include `0xd3adbeef`)
-    pub fn list<T: Table>(&self, filter: &ListFilter) -> Result<(Vec<TableRow<T>>, usize)> {
+    ///
+    /// Fails on the first row that can't be read or decoded, unless [`ListFilter::report_errors`]
+    /// is set, in which case such rows are returned as [`RowError`]s and listing continues. Rows
+    /// that fail to decode are not counted as hits. With [`ListFilter::only_count`], rows are only
+    /// decoded if errors are reported.
+    pub fn list<T: Table>(
+        &self,
+        filter: &ListFilter,
+    ) -> Result<(Vec<TableRow<T>>, Vec<RowError>, usize)> {
+        self.ensure_table_allowed::<T>()?;
+
         let bmb = Rc::new(BMByte::from(&filter.search));
         if bmb.is_none() && filter.has_search() {
             eyre::bail!("Invalid search.")
         }
 
         let mut hits = 0;
+        let mut errors = Vec::new();
 
         let data = self.db.view(|tx| {
             let mut cursor =
                 tx.cursor_read::<RawTable<T>>().expect("Was not able to obtain a cursor.");
 
-            let map_filter = |row: Result<TableRawRow<T>, _>| {
-                if let Ok((k, v)) = row {
-                    let (key, value) = (k.into_key(), v.into_value());
-
-                    if key.len() + value.len() < filter.min_row_size {
-                        return None
-                    }
-                    if key.len() < filter.min_key_size {
-                        return None
-                    }
-                    if value.len() < filter.min_value_size {
-                        return None
-                    }
+            // Returns the error if it should fail the listing, otherwise collects it.
+            let mut on_error = |row_error: RowError| {
+                if filter.report_errors {
+                    errors.push(row_error);
+                    return None
+                }
+                Some(Err(row_error))
+            };
 
-                    let result = || {
-                        if filter.only_count {
-                            return None
-                        }
-                        Some((
-                            <T as Table>::Key::decode(&key).unwrap(),
-                            <T as Table>::Value::decompress(&value).unwrap(),
-                        ))
-                    };
+            let mut map_filter = |row: Result<TableRawRow<T>, _>| {
+                let (k, v) = match row {
+                    Ok(row) => row,
+                    Err(error) => return on_error(RowError { key: Vec::new(), error }),
+                };
+                let (key, value) = (k.into_key(), v.into_value());
+                if !filter.matches_raw((*bmb).as_ref(), &key, &value) {
+                    return None
+                }
 
-                    match &*bmb {
-                        Some(searcher) => {
-                            if searcher.find_first_in(&value).is_some() ||
-                                searcher.find_first_in(&key).is_some()
-                            {
-                                hits += 1;
-                                return result()
-                            }
-                        }
-                        None => {
-                            hits += 1;
-                            return result()
-                        }
+                // Counting without reporting errors doesn't need to decode the row.
+                if filter.only_count && !filter.report_errors {
+                    hits += 1;
+                    return None
+                }
+                let decoded = <T as Table>::Key::decode(&key).and_then(|decoded_key| {
+                    Ok((decoded_key, <T as Table>::Value::decompress(&value)?))
+                });
+                match decoded {
+                    Ok(row) => {
+                        hits += 1;
+                        (!filter.only_count).then_some(Ok(row))
                     }
+                    Err(error) => on_error(RowError { key, error }),
                 }
-                None
             };
 
             if filter.reverse {
                 Ok(cursor
                     .walk_back(None)?
                     .skip(filter.skip)
-                    .filter_map(map_filter)
+                    .filter_map(&mut map_filter)
                     .take(filter.len)
-                    .collect::<Vec<(_, _)>>())
+                    .collect::<Result<Vec<(_, _)>, _>>())
             } else {
                 Ok(cursor
                     .walk(None)?
                     .skip(filter.skip)
-                    .filter_map(map_filter)
+                    .filter_map(&mut map_filter)
                     .take(filter.len)
-                    .collect::<Vec<(_, _)>>())
+                    .collect::<Result<Vec<(_, _)>, _>>())
             }
         })?;
 
-        Ok((data.map_err(|e: DatabaseError| eyre::eyre!(e))?, hits))
+        let data = data.map_err(|e: DatabaseError| eyre::eyre!(e))?.map_err(|row_error| {
+            eyre::eyre!(
+                "Failed to read row with key 0x{} from table {}: {}",
+                hex::encode(&row_error.key),
+                T::NAME,
+                row_error.error
+            )
+        })?;
+
+        Ok((data, errors, hits))
+    }
+
+    /// Streams the rows of table `T` matching `filter` to `writer` as MessagePack, returning the
+    /// number of exported rows.
+    ///
+    /// Rows are not decoded: each one is written as a `{"key": bin, "value": bin}` map holding the
+    /// exact bytes stored in the database, so [`ListFilter::only_count`] and
+    /// [`ListFilter::report_errors`] don't apply.
+    pub fn export_msgpack<T: Table>(
+        &self,
+        mut writer: impl io::Write,
+        filter: &ListFilter,
+    ) -> Result<usize> {
+        self.ensure_table_allowed::<T>()?;
+
+        let bmb = BMByte::from(&filter.search);
+        if bmb.is_none() && filter.has_search() {
+            eyre::bail!("Invalid search.")
+        }
+
+        self.db.view(|tx| {
+            let mut cursor = tx.cursor_read::<RawTable<T>>()?;
+            let walker: Box<dyn Iterator<Item = Result<TableRawRow<T>, DatabaseError>> + '_> =
+                if filter.reverse {
+                    Box::new(cursor.walk_back(None)?)
+                } else {
+                    Box::new(cursor.walk(None)?)
+                };
+
+            let mut exported = 0;
+            for row in walker.skip(filter.skip) {
+                if exported == filter.len {
+                    break
+                }
+                let (k, v) = row?;
+                let (key, value) = (k.into_key(), v.into_value());
+                if !filter.matches_raw(bmb.as_ref(), &key, &value) {
+                    continue
+                }
+                write_msgpack_row(&mut writer, &key, &value)?;
+                exported += 1;
+            }
+            writer.flush()?;
+            Ok::<_, eyre::Report>(exported)
+        })?
     }
 
     /// Grabs the content of the table for the given key
     pub fn get<T: Table>(&self, key: T::Key) -> Result<Option<T::Value>> {
+        self.ensure_table_allowed::<T>()?;
         self.db.view(|tx| tx.get::<T>(key))?.map_err(|e| eyre::eyre!(e))
     }
 
+    /// Parses a user-provided key for table `T`.
+    ///
+    /// Besides everything accepted by [`parse_table_key`], `latest` resolves to the last key
+    /// currently stored in the table.
+    pub fn parse_key<T: Table>(&self, input: &str) -> Result<T::Key> {
+        if input != "latest" {
+            return parse_table_key::<T>(input)
+        }
+        self.latest_key::<T>()
+    }
+
+    /// Returns the last key currently stored in table `T`, failing if the table is empty.
+    pub fn latest_key<T: Table>(&self) -> Result<T::Key> {
+        self.ensure_table_allowed::<T>()?;
+
+        self.db
+            .view(|tx| tx.cursor_read::<T>()?.last())?
+            .map_err(|e| eyre::eyre!(e))?
+            .map(|(key, _)| key)
+            .ok_or_else(|| eyre::eyre!("Table {} is empty", T::NAME))
+    }
+
+    /// Finds the highest block at which the account at `address` changed, together with the
+    /// account state before and after that change.
+    ///
+    /// Changes up to the [`StageId::IndexAccountHistory`] checkpoint are looked up in the
+    /// [`AccountHistory`] index. Changes after it, e.g. while the history stages lag behind
+    /// execution during sync, are found by walking the [`AccountChangeSet`]s from the checkpoint
+    /// to the tip. Returns `None` if the account never changed or if its history was pruned.
+    pub fn last_change_block(&self, address: Address) -> Result<Option<AccountChange>> {
+        self.ensure_tables_allowed(&[
+            SyncStage::NAME,
+            AccountChangeSet::NAME,
+            AccountHistory::NAME,
+            PlainAccountState::NAME,
+        ])?;
+
+        self.db
+            .view(|tx| {
+                let mut changesets = tx.cursor_dup_read::<AccountChangeSet>()?;
+
+                let first_unindexed = tx
+                    .get::<SyncStage>(StageId::IndexAccountHistory.to_string())?
+                    .map_or(0, |checkpoint| checkpoint.block_number + 1);
+                let mut unindexed = None;
+                for entry in changesets.walk_range(first_unindexed..)? {
+                    let (block_number, entry) = entry?;
+                    if entry.address == address {
+                        unindexed = Some((block_number, entry.info));
+                    }
+                }
+
+                let (block_number, before) = match unindexed {
+                    Some(change) => change,
+                    None => {
+                        // The last shard of an account's history is always keyed by `u64::MAX`.
+                        let Some(list) =
+                            tx.get::<AccountHistory>(ShardedKey::new(address, u64::MAX))?
+                        else {
+                            return Ok(None)
+                        };
+                        let Some(block_number) = list.iter(0).last() else { return Ok(None) };
+                        let block_number = block_number as BlockNumber;
+
+                        let before = changesets
+                            .seek_by_key_subkey(block_number, address)?
+                            .filter(|entry| entry.address == address)
+                            .and_then(|entry| entry.info);
+                        (block_number, before)
+                    }
+                };
+                // No changeset after this block touches the account, so the plain state holds the
+                // value it was changed to.
+                let after = tx.get::<PlainAccountState>(address)?;
+
+                Ok(Some(AccountChange { block_number, before, after }))
+            })?
+            .map_err(|e: DatabaseError| eyre::eyre!(e))
+    }
+
+    /// Returns all populated storage slots of the account at `address` from the plain state,
+    /// ordered by slot.
+    pub fn account_storage(&self, address: Address) -> Result<Vec<(B256, U256)>> {
+        self.ensure_table_allowed::<PlainStorageState>()?;
+
+        self.db
+            .view(|tx| {
+                let mut cursor = tx.cursor_dup_read::<PlainStorageState>()?;
+                let mut slots = Vec::new();
+                // If the account has no storage, the walker starts at the next address.
+                for entry in cursor.walk_dup(Some(address), None)? {
+                    let (key, entry) = entry?;
+                    if key != address {
+                        break
+                    }
+                    slots.push((entry.key, entry.value));
+                }
+                Ok(slots)
+            })?
+            .map_err(|e: DatabaseError| eyre::eyre!(e))
+    }
+
+    /// Assembles the block with the given hash or number from the header, transactions, ommers
+    /// and withdrawals stored in the database.
+    ///
+    /// This is the local counterpart to [`get_single_body`].
+    pub fn sealed_block(&self, id: BlockHashOrNumber) -> Result<Option<SealedBlock>> {
+        self.ensure_tables_allowed(&[
+            HeaderNumbers::NAME,
+            Headers::NAME,
+            BlockBodyIndices::NAME,
+            Transactions::NAME,
+            BlockOmmers::NAME,
+            BlockWithdrawals::NAME,
+        ])?;
+
+        let factory = ProviderFactory::new(self.db, self.chain.clone());
+        let provider = factory.provider()?;
+        Ok(provider.block(id)?.map(|block| block.seal_slow()))
+    }
+
+    /// Runs all [`Invariant`]s against the database and returns every violation found.
+    pub fn check_invariants(&self) -> Result<Vec<InvariantViolation>> {
+        self.check_invariants_with(&Invariant::ALL)
+    }
+
+    /// Runs the given [`Invariant`]s against the database and returns every violation found.
+    ///
+    /// Tables are walked with cursors, so memory usage only grows with the number of violations.
+    pub fn check_invariants_with(
+        &self,
+        invariants: &[Invariant],
+    ) -> Result<Vec<InvariantViolation>> {
+        for invariant in invariants {
+            self.ensure_tables_allowed(invariant.tables())?;
+        }
+
+        self.db
+            .view(|tx| {
+                let mut violations = Vec::new();
+                for invariant in invariants {
+                    info!(target: "reth::cli", ?invariant, "Checking invariant");
+                    invariant.check(tx, &mut violations)?;
+                }
+                Ok(violations)
+            })?
+            .map_err(|e: DatabaseError| eyre::eyre!(e))
+    }
+}
+
+impl<'a, DB: Database> DbTool<'a, DB, ReadWrite> {
+    /// Writes the bytecode of every contract in the [`Bytecodes`] table to a file in `path`, named
+    /// by its code hash.
+    ///
+    /// If `only_referenced` is set, only contracts referenced by at least one account in the plain
+    /// state are extracted. If `disassemble` is set, a listing of the opcodes is written to a
+    /// `.asm` file instead of the raw bytes to a `.bin` file.
+    ///
+    /// Returns the number of extracted contracts.
+    pub fn extract_contracts(
+        &self,
+        path: impl AsRef<Path>,
+        only_referenced: bool,
+        disassemble: bool,
+    ) -> Result<usize> {
+        let path = path.as_ref();
+        fs::create_dir_all(path)?;
+
+        self.db.view(|tx| {
+            let referenced = if only_referenced {
+                let mut cursor = tx.cursor_read::<PlainAccountState>()?;
+                let mut referenced = HashSet::new();
+                for entry in cursor.walk(None)? {
+                    if let Some(hash) = entry?.1.bytecode_hash {
+                        referenced.insert(hash);
+                    }
+                }
+                Some(referenced)
+            } else {
+                None
+            };
+
+            let mut cursor = tx.cursor_read::<Bytecodes>()?;
+            let mut extracted = 0;
+            for entry in cursor.walk(None)? {
+                let (hash, bytecode) = entry?;
+                if referenced.as_ref().is_some_and(|referenced| !referenced.contains(&hash)) {
+                    continue
+                }
+
+                let code = bytecode.original_bytes();
+                if disassemble {
+                    fs::write(path.join(format!("{hash:?}.asm")), disassemble_bytecode(&code))?;
+                } else {
+                    fs::write(path.join(format!("{hash:?}.bin")), code)?;
+                }
+                extracted += 1;
+            }
+
+            info!(target: "reth::cli", extracted, ?path, "Extracted contracts");
+            Ok::<_, eyre::Report>(extracted)
+        })?
+    }
+
     /// Drops the database at the given path.
     pub fn drop(&mut self, path: impl AsRef<Path>) -> Result<()> {
         let path = path.as_ref();
@@ -208,6 +797,198 @@ impl<'a, DB: Database> DbTool<'a, DB> {
     }
 }
 
+/// A cross-table consistency check run by [`DbTool::check_invariants`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Invariant {
+    /// Every [`CanonicalHeaders`] entry has a header in [`Headers`].
+    CanonicalHeadersHaveHeaders,
+    /// Every [`HeaderNumbers`] entry points to a header in [`Headers`].
+    HeaderNumbersHaveHeaders,
+    /// Every [`TxHashNumber`] entry points to a transaction in [`Transactions`].
+    TxHashNumbersHaveTransactions,
+    /// Every bytecode hash in [`PlainAccountState`] has its bytecode in [`Bytecodes`].
+    AccountsHaveBytecodes,
+}
+
+impl Invariant {
+    /// All available invariants.
+    pub const ALL: [Invariant; 4] = [
+        Invariant::CanonicalHeadersHaveHeaders,
+        Invariant::HeaderNumbersHaveHeaders,
+        Invariant::TxHashNumbersHaveTransactions,
+        Invariant::AccountsHaveBytecodes,
+    ];
+
+    /// The names of the tables the invariant reads.
+    fn tables(&self) -> &'static [&'static str] {
+        match self {
+            Invariant::CanonicalHeadersHaveHeaders => &[CanonicalHeaders::NAME, Headers::NAME],
+            Invariant::HeaderNumbersHaveHeaders => &[HeaderNumbers::NAME, Headers::NAME],
+            Invariant::TxHashNumbersHaveTransactions => &[TxHashNumber::NAME, Transactions::NAME],
+            Invariant::AccountsHaveBytecodes => &[PlainAccountState::NAME, Bytecodes::NAME],
+        }
+    }
+
+    /// Walks the source table of the invariant and records every entry that violates it.
+    fn check<TX: DbTx>(
+        &self,
+        tx: &TX,
+        violations: &mut Vec<InvariantViolation>,
+    ) -> Result<(), DatabaseError> {
+        let mut violation =
+            |message: String| violations.push(InvariantViolation { invariant: *self, message });
+
+        match self {
+            Invariant::CanonicalHeadersHaveHeaders => {
+                for entry in tx.cursor_read::<CanonicalHeaders>()?.walk(None)? {
+                    let (number, hash) = entry?;
+                    if tx.get::<Headers>(number)?.is_none() {
+                        violation(format!("missing header for canonical block {number} ({hash})"));
+                    }
+                }
+            }
+            Invariant::HeaderNumbersHaveHeaders => {
+                for entry in tx.cursor_read::<HeaderNumbers>()?.walk(None)? {
+                    let (hash, number) = entry?;
+                    if tx.get::<Headers>(number)?.is_none() {
+                        violation(format!("missing header for block {number} ({hash})"));
+                    }
+                }
+            }
+            Invariant::TxHashNumbersHaveTransactions => {
+                for entry in tx.cursor_read::<TxHashNumber>()?.walk(None)? {
+                    let (hash, tx_number) = entry?;
+                    if tx.get::<Transactions>(tx_number)?.is_none() {
+                        violation(format!("missing transaction {tx_number} ({hash})"));
+                    }
+                }
+            }
+            Invariant::AccountsHaveBytecodes => {
+                for entry in tx.cursor_read::<PlainAccountState>()?.walk(None)? {
+                    let (address, account) = entry?;
+                    let Some(hash) = account.bytecode_hash.filter(|hash| *hash != KECCAK_EMPTY)
+                    else {
+                        continue
+                    };
+                    if tx.get::<Bytecodes>(hash)?.is_none() {
+                        violation(format!("missing bytecode {hash} of account {address}"));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A database entry violating an [`Invariant`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvariantViolation {
+    /// The violated invariant.
+    pub invariant: Invariant,
+    /// Describes the offending entry.
+    pub message: String,
+}
+
+/// Renders a listing of the opcodes in `code`, one instruction per line prefixed by its offset.
+///
+/// Push data is printed as hex next to its opcode, unknown opcodes are printed as `INVALID(0x..)`.
+pub fn disassemble_bytecode(code: &[u8]) -> String {
+    let mut listing = String::new();
+    let mut pc = 0;
+    while pc < code.len() {
+        let op = code[pc];
+        let _ = match OpCode::new(op) {
+            Some(opcode) => write!(listing, "{pc:#06x}: {opcode}"),
+            None => write!(listing, "{pc:#06x}: INVALID({op:#04x})"),
+        };
+        pc += 1;
+
+        if (opcode::PUSH1..=opcode::PUSH32).contains(&op) {
+            let size = (op - opcode::PUSH1 + 1) as usize;
+            let data = &code[pc..(pc + size).min(code.len())];
+            let _ = write!(listing, " 0x{}", hex::encode(data));
+            pc += size;
+        }
+        listing.push('\n');
+    }
+    listing
+}
+
+/// The most recent change to an account, as returned by [`DbTool::last_change_block`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountChange {
+    /// The block in which the account was changed.
+    pub block_number: BlockNumber,
+    /// The account state before the change. `None` if the account did not exist.
+    pub before: Option<Account>,
+    /// The account state after the change. `None` if the account was destroyed.
+    pub after: Option<Account>,
+}
+
+/// Parses a user-provided key for table `T`.
+///
+/// The input can be the JSON representation of the key, a decimal or `0x` prefixed hex number for
+/// integer keys (e.g. `123` or `0x7b`), or an unquoted string for keys that deserialize from one
+/// (e.g. `0x` prefixed hashes and addresses, or stage names).
+pub fn parse_table_key<T: Table>(input: &str) -> Result<T::Key> {
+    if let Ok(key) = serde_json::from_str::<T::Key>(input) {
+        return Ok(key)
+    }
+
+    let as_string = serde_json::from_value::<T::Key>(serde_json::Value::String(input.to_string()));
+    if let Ok(key) = as_string {
+        return Ok(key)
+    }
+
+    let number = input.strip_prefix("0x").and_then(|hex| u64::from_str_radix(hex, 16).ok());
+    if let Some(number) = number {
+        if let Ok(key) = serde_json::from_value::<T::Key>(number.into()) {
+            return Ok(key)
+        }
+    }
+
+    // Report the error of the most likely interpretation of the input.
+    as_string.map_err(|e| eyre::eyre!("Invalid key {input:?} for table {}: {e}", T::NAME))
+}
+
+/// Writes a raw table row as the MessagePack map `{"key": bin, "value": bin}`.
+fn write_msgpack_row(writer: &mut impl io::Write, key: &[u8], value: &[u8]) -> io::Result<()> {
+    // fixmap with two entries, followed by the fixstr "key"
+    writer.write_all(b"\x82\xa3key")?;
+    write_msgpack_bin(writer, key)?;
+    // fixstr "value"
+    writer.write_all(b"\xa5value")?;
+    write_msgpack_bin(writer, value)
+}
+
+/// Writes `bytes` using the smallest MessagePack bin format that fits them.
+fn write_msgpack_bin(writer: &mut impl io::Write, bytes: &[u8]) -> io::Result<()> {
+    let len = bytes.len();
+    if let Ok(len) = u8::try_from(len) {
+        writer.write_all(&[0xc4, len])?;
+    } else if let Ok(len) = u16::try_from(len) {
+        writer.write_all(&[0xc5])?;
+        writer.write_all(&len.to_be_bytes())?;
+    } else {
+        let len = u32::try_from(len).map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidInput, "value too large for MessagePack bin")
+        })?;
+        writer.write_all(&[0xc6])?;
+        writer.write_all(&len.to_be_bytes())?;
+    }
+    writer.write_all(bytes)
+}
+
+/// A row that [`DbTool::list`] could not read or decode.
+#[derive(Debug)]
+pub struct RowError {
+    /// The raw key of the row. Empty if the row could not be read from the database at all.
+    pub key: Vec<u8>,
+    /// The error encountered while reading or decoding the row.
+    pub error: DatabaseError,
+}
+
 /// Parses a user-specified path with support for environment variables and common shorthands (e.g.
 /// ~ for the user's home directory).
 pub fn parse_path(value: &str) -> Result<PathBuf, shellexpand::LookupError<VarError>> {
@@ -215,7 +996,7 @@ pub fn parse_path(value: &str) -> Result<PathBuf, shellexpand::LookupError<VarEr
 }
 
 /// Filters the results coming from the database.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ListFilter {
     /// Skip first N entries.
     pub skip: usize,
@@ -233,17 +1014,494 @@ pub struct ListFilter {
     pub reverse: bool,
     /// Only counts the number of filtered entries without decoding and returning them.
     pub only_count: bool,
+    /// Returns rows that can't be read or decoded as errors instead of silently skipping them.
+    pub report_errors: bool,
 }
 
 impl ListFilter {
+    /// Sets `search` to the bytes of the given hex string, which may be prefixed with `0x`.
+    ///
+    /// Returns an error if the string is empty, has an odd length or contains non-hex characters.
+    pub fn with_search_hex(mut self, search: &str) -> Result<Self> {
+        let hex = search.strip_prefix("0x").unwrap_or(search);
+        if hex.is_empty() {
+            eyre::bail!("Invalid hex search `{search}`: no bytes to search for")
+        }
+        self.search =
+            hex::decode(hex).map_err(|err| eyre::eyre!("Invalid hex search `{search}`: {err}"))?;
+        Ok(self)
+    }
+
     /// If `search` has a list of bytes, then filter for rows that have this sequence.
     pub fn has_search(&self) -> bool {
         !self.search.is_empty()
     }
 
+    /// Whether a raw row passes the size limits and, if a searcher is given, contains the search
+    /// bytes in its key or value.
+    fn matches_raw(&self, searcher: Option<&BMByte>, key: &[u8], value: &[u8]) -> bool {
+        if key.len() + value.len() < self.min_row_size ||
+            key.len() < self.min_key_size ||
+            value.len() < self.min_value_size
+        {
+            return false
+        }
+        searcher.map_or(true, |searcher| {
+            searcher.find_first_in(value).is_some() || searcher.find_first_in(key).is_some()
+        })
+    }
+
     /// Updates the page with new `skip` and `len` values.
     pub fn update_page(&mut self, skip: usize, len: usize) {
         self.skip = skip;
         self.len = len;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::future::{self, Pending};
+    use reth_db::{
+        models::AccountBeforeTx, test_utils::create_test_rw_db, BlockNumberList, RawKey, RawValue,
+    };
+    use reth_interfaces::{
+        consensus::ConsensusError,
+        p2p::{download::DownloadClient, error::PeerRequestResult},
+        test_utils::{
+            generators::{self, random_block},
+            TestBodiesClient, TestHeadersClient,
+        },
+    };
+    use reth_primitives::{
+        stage::StageCheckpoint, BlockBody, Bytecode, Bytes, StorageEntry, MAINNET,
+    };
+    use reth_provider::BlockWriter;
+
+    #[test]
+    fn parse_latest_key() {
+        let db = create_test_rw_db();
+        let tool = DbTool::new(&db, MAINNET.clone()).unwrap();
+
+        let err = tool.parse_key::<Headers>("latest").unwrap_err();
+        assert_eq!(err.to_string(), "Table Headers is empty");
+
+        db.update(|tx| {
+            tx.put::<Headers>(1, Default::default())?;
+            tx.put::<Headers>(5, Default::default())
+        })
+        .unwrap()
+        .unwrap();
+        assert_eq!(tool.parse_key::<Headers>("latest").unwrap(), 5);
+        assert_eq!(tool.parse_key::<Headers>("1").unwrap(), 1);
+    }
+
+    /// A filter that returns the first 5 rows.
+    fn list_filter() -> ListFilter {
+        ListFilter {
+            skip: 0,
+            len: 5,
+            search: vec![],
+            min_row_size: 0,
+            min_key_size: 0,
+            min_value_size: 0,
+            reverse: false,
+            only_count: false,
+            report_errors: false,
+        }
+    }
+
+    #[test]
+    fn last_change_block() {
+        let db = create_test_rw_db();
+        let address = Address::with_last_byte(1);
+        let first = Account { nonce: 1, ..Default::default() };
+        let second = Account { nonce: 2, ..Default::default() };
+
+        db.update(|tx| {
+            tx.put::<AccountHistory>(
+                ShardedKey::new(address, u64::MAX),
+                BlockNumberList::new_pre_sorted([3, 7]),
+            )?;
+            tx.put::<AccountChangeSet>(3, AccountBeforeTx { address, info: None })?;
+            tx.put::<AccountChangeSet>(7, AccountBeforeTx { address, info: Some(first) })?;
+            tx.put::<PlainAccountState>(address, second)
+        })
+        .unwrap()
+        .unwrap();
+
+        let tool = DbTool::new(&db, MAINNET.clone()).unwrap();
+        assert_eq!(
+            tool.last_change_block(address).unwrap(),
+            Some(AccountChange { block_number: 7, before: Some(first), after: Some(second) })
+        );
+        assert_eq!(tool.last_change_block(Address::with_last_byte(2)).unwrap(), None);
+    }
+
+    #[test]
+    fn last_change_block_unindexed() {
+        let db = create_test_rw_db();
+        let address = Address::with_last_byte(1);
+        let first = Account { nonce: 1, ..Default::default() };
+        let second = Account { nonce: 2, ..Default::default() };
+        let third = Account { nonce: 3, ..Default::default() };
+
+        // The history index only covers blocks up to 5, the change at block 9 is not indexed yet.
+        db.update(|tx| {
+            tx.put::<SyncStage>(
+                StageId::IndexAccountHistory.to_string(),
+                StageCheckpoint { block_number: 5, ..Default::default() },
+            )?;
+            tx.put::<AccountHistory>(
+                ShardedKey::new(address, u64::MAX),
+                BlockNumberList::new_pre_sorted([3]),
+            )?;
+            tx.put::<AccountChangeSet>(3, AccountBeforeTx { address, info: Some(first) })?;
+            tx.put::<AccountChangeSet>(9, AccountBeforeTx { address, info: Some(second) })?;
+            tx.put::<PlainAccountState>(address, third)
+        })
+        .unwrap()
+        .unwrap();
+
+        let tool = DbTool::new(&db, MAINNET.clone()).unwrap();
+        assert_eq!(
+            tool.last_change_block(address).unwrap(),
+            Some(AccountChange { block_number: 9, before: Some(second), after: Some(third) })
+        );
+    }
+
+    #[test]
+    fn account_storage() {
+        let db = create_test_rw_db();
+        let address = Address::with_last_byte(1);
+        let slots =
+            [(B256::with_last_byte(1), U256::from(10)), (B256::with_last_byte(2), U256::from(20))];
+
+        db.update(|tx| {
+            for (key, value) in slots {
+                tx.put::<PlainStorageState>(address, StorageEntry { key, value })?;
+            }
+            tx.put::<PlainStorageState>(
+                Address::with_last_byte(3),
+                StorageEntry { key: B256::with_last_byte(1), value: U256::from(30) },
+            )
+        })
+        .unwrap()
+        .unwrap();
+
+        let tool = DbTool::new(&db, MAINNET.clone()).unwrap();
+        assert_eq!(tool.account_storage(address).unwrap(), slots);
+        assert!(tool.account_storage(Address::with_last_byte(2)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn sealed_block() {
+        let db = create_test_rw_db();
+        let mut rng = generators::rng();
+        let block = random_block(&mut rng, 1, None, Some(2), Some(1));
+
+        let factory = ProviderFactory::new(&db, MAINNET.clone());
+        let provider = factory.provider_rw().unwrap();
+        provider.insert_block(block.clone(), None, None).unwrap();
+        provider.commit().unwrap();
+
+        let tool = DbTool::new(&db, MAINNET.clone()).unwrap();
+        let by_number = tool.sealed_block(BlockHashOrNumber::Number(1)).unwrap().unwrap();
+        assert_eq!(by_number.hash(), block.hash());
+        assert_eq!(by_number, block);
+        assert_eq!(tool.sealed_block(BlockHashOrNumber::Hash(block.hash())).unwrap(), Some(block));
+        assert_eq!(tool.sealed_block(BlockHashOrNumber::Number(2)).unwrap(), None);
+
+        let tool = DbTool::new_readonly(&db, MAINNET.clone(), Some(vec![Tables::Headers])).unwrap();
+        assert!(tool.sealed_block(BlockHashOrNumber::Number(1)).is_err());
+    }
+
+    #[test]
+    fn check_invariants() {
+        let db = create_test_rw_db();
+        let code_hash = B256::with_last_byte(0xc0);
+        let account = Account { bytecode_hash: Some(code_hash), ..Default::default() };
+
+        db.update(|tx| {
+            tx.put::<CanonicalHeaders>(0, B256::with_last_byte(1))?;
+            tx.put::<Headers>(0, Default::default())?;
+            tx.put::<CanonicalHeaders>(1, B256::with_last_byte(2))?;
+            tx.put::<PlainAccountState>(Address::with_last_byte(1), account)
+        })
+        .unwrap()
+        .unwrap();
+
+        let tool = DbTool::new(&db, MAINNET.clone()).unwrap();
+        let violations = tool.check_invariants().unwrap();
+        assert_eq!(
+            violations.iter().map(|violation| violation.invariant).collect::<Vec<_>>(),
+            vec![Invariant::CanonicalHeadersHaveHeaders, Invariant::AccountsHaveBytecodes]
+        );
+    }
+
+    #[tokio::test]
+    async fn header_cache() {
+        let client = TestHeadersClient::default();
+        let header = Header { number: 1, ..Default::default() };
+        client.extend([header.clone()]).await;
+
+        let mut cache = HeaderCache::default();
+        let fetched = cache.get_single_header(&client, BlockHashOrNumber::Number(1)).await.unwrap();
+        assert_eq!(fetched, header.clone().seal_slow());
+
+        // served from the cache
+        let cached =
+            cache.get_single_header(&client, BlockHashOrNumber::Hash(fetched.hash)).await.unwrap();
+        assert_eq!(cached, fetched);
+        assert_eq!(client.request_attempts(), 1);
+
+        cache.invalidate(BlockHashOrNumber::Number(1));
+        assert!(cache.get(BlockHashOrNumber::Hash(fetched.hash)).is_none());
+        client.extend([header]).await;
+        cache.get_single_header(&client, BlockHashOrNumber::Number(1)).await.unwrap();
+        assert_eq!(client.request_attempts(), 2);
+    }
+
+    /// A client whose requests never complete.
+    #[derive(Debug)]
+    struct PendingHeadersClient;
+
+    impl DownloadClient for PendingHeadersClient {
+        fn report_bad_message(&self, _peer_id: PeerId) {}
+
+        fn num_connected_peers(&self) -> usize {
+            0
+        }
+    }
+
+    impl HeadersClient for PendingHeadersClient {
+        type Output = Pending<PeerRequestResult<Vec<Header>>>;
+
+        fn get_headers_with_priority(
+            &self,
+            _request: HeadersRequest,
+            _priority: Priority,
+        ) -> Self::Output {
+            future::pending()
+        }
+    }
+
+    #[tokio::test]
+    async fn single_header_timeout() {
+        let err = get_single_header_with_timeout(
+            PendingHeadersClient,
+            BlockHashOrNumber::Number(1),
+            Duration::from_millis(10),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().starts_with("Timed out after 10ms"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn single_body_skip_validation() {
+        // The default header doesn't commit to the ommers of the empty body.
+        let header = Header::default().seal_slow();
+        let client = || TestBodiesClient {
+            responder: |_: Vec<B256>| -> PeerRequestResult<Vec<BlockBody>> {
+                Ok((PeerId::default(), vec![BlockBody::default()]).into())
+            },
+        };
+
+        let err = get_single_body(client(), MAINNET.clone(), header.clone()).await.unwrap_err();
+        assert!(err.downcast_ref::<ConsensusError>().is_some(), "{err}");
+
+        let block = get_single_body_with_timeout(
+            client(),
+            MAINNET.clone(),
+            header.clone(),
+            DEFAULT_REQUEST_TIMEOUT,
+            true,
+        )
+        .await
+        .unwrap();
+        assert_eq!(block.header, header);
+        assert!(block.body.is_empty());
+    }
+
+    #[tokio::test]
+    async fn header_cache_forks() {
+        let client = TestHeadersClient::default();
+        let canonical = Header { number: 1, ..Default::default() };
+        let fork = Header { number: 1, gas_limit: 1, ..Default::default() };
+        let fork_hash = fork.hash_slow();
+        client.extend([canonical.clone(), fork]).await;
+
+        let mut cache = HeaderCache::default();
+        let canonical =
+            cache.get_single_header(&client, BlockHashOrNumber::Number(1)).await.unwrap();
+        let fork =
+            cache.get_single_header(&client, BlockHashOrNumber::Hash(fork_hash)).await.unwrap();
+        assert_eq!(fork.hash, fork_hash);
+
+        // the side fork header doesn't replace the header the number resolves to
+        assert_eq!(cache.get(BlockHashOrNumber::Number(1)), Some(&canonical));
+
+        cache.invalidate(BlockHashOrNumber::Hash(fork_hash));
+        assert!(cache.get(BlockHashOrNumber::Hash(fork_hash)).is_none());
+        assert_eq!(cache.get(BlockHashOrNumber::Number(1)), Some(&canonical));
+    }
+
+    #[test]
+    fn readonly_allowed_tables() {
+        let db = create_test_rw_db();
+        let tool = DbTool::new_readonly(&db, MAINNET.clone(), Some(vec![Tables::Headers])).unwrap();
+        assert!(tool.get::<Headers>(0).unwrap().is_none());
+        assert!(tool.get::<PlainAccountState>(Address::ZERO).is_err());
+
+        let err = tool.account_storage(Address::ZERO).unwrap_err();
+        assert_eq!(err.to_string(), "Access to table PlainStorageState is not allowed.");
+        assert!(tool.last_change_block(Address::ZERO).is_err());
+        assert!(tool.check_invariants().is_err());
+        assert!(tool.check_invariants_with(&[Invariant::CanonicalHeadersHaveHeaders]).is_err());
+
+        let tool = DbTool::new_readonly(
+            &db,
+            MAINNET.clone(),
+            Some(vec![Tables::CanonicalHeaders, Tables::Headers]),
+        )
+        .unwrap();
+        assert!(tool
+            .check_invariants_with(&[Invariant::CanonicalHeadersHaveHeaders])
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn list_row_errors() {
+        let db = create_test_rw_db();
+        let bad_key = RawKey::<BlockNumber>::decode(vec![1, 2, 3]).unwrap();
+
+        db.update(|tx| {
+            tx.put::<CanonicalHeaders>(1, B256::with_last_byte(1))?;
+            tx.put::<RawTable<CanonicalHeaders>>(bad_key, RawValue::new(B256::with_last_byte(2)))
+        })
+        .unwrap()
+        .unwrap();
+
+        let tool = DbTool::new(&db, MAINNET.clone()).unwrap();
+        let mut filter = list_filter();
+
+        let err = tool.list::<CanonicalHeaders>(&filter).unwrap_err();
+        assert!(err.to_string().starts_with("Failed to read row with key 0x010203"), "{err}");
+
+        filter.report_errors = true;
+        let (rows, errors, hits) = tool.list::<CanonicalHeaders>(&filter).unwrap();
+        assert_eq!(rows, vec![(1, B256::with_last_byte(1))]);
+        assert_eq!(hits, 1);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].key, vec![1, 2, 3]);
+        assert_eq!(errors[0].error, DatabaseError::Decode);
+
+        filter.only_count = true;
+        let (rows, errors, hits) = tool.list::<CanonicalHeaders>(&filter).unwrap();
+        assert!(rows.is_empty());
+        assert_eq!(hits, 1);
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn export_msgpack() {
+        let db = create_test_rw_db();
+        let bad_key = RawKey::<BlockNumber>::decode(vec![1, 2, 3]).unwrap();
+
+        db.update(|tx| {
+            tx.put::<CanonicalHeaders>(1, B256::with_last_byte(1))?;
+            tx.put::<RawTable<CanonicalHeaders>>(bad_key, RawValue::new(B256::with_last_byte(2)))
+        })
+        .unwrap()
+        .unwrap();
+
+        let row = |key: &[u8], value: B256| {
+            let mut row = vec![0x82, 0xa3, b'k', b'e', b'y', 0xc4, key.len() as u8];
+            row.extend_from_slice(key);
+            row.extend_from_slice(&[0xa5, b'v', b'a', b'l', b'u', b'e', 0xc4, 32]);
+            row.extend_from_slice(value.as_slice());
+            row
+        };
+
+        let tool = DbTool::new(&db, MAINNET.clone()).unwrap();
+        let mut filter = list_filter();
+
+        // Undecodable rows are exported as stored.
+        let mut out = Vec::new();
+        assert_eq!(tool.export_msgpack::<CanonicalHeaders>(&mut out, &filter).unwrap(), 2);
+        let mut expected = row(&1u64.to_be_bytes(), B256::with_last_byte(1));
+        expected.extend(row(&[1, 2, 3], B256::with_last_byte(2)));
+        assert_eq!(out, expected);
+
+        filter.search = vec![1, 2, 3];
+        let mut out = Vec::new();
+        assert_eq!(tool.export_msgpack::<CanonicalHeaders>(&mut out, &filter).unwrap(), 1);
+        assert_eq!(out, row(&[1, 2, 3], B256::with_last_byte(2)));
+
+        let mut out = Vec::new();
+        write_msgpack_bin(&mut out, &[0; 256]).unwrap();
+        assert_eq!(out[..3], [0xc5, 0x01, 0x00]);
+        assert_eq!(out.len(), 3 + 256);
+    }
+
+    #[test]
+    fn list_filter_search_hex() {
+        let filter = list_filter();
+
+        let filter = filter.with_search_hex("0xd3adbeef").unwrap();
+        assert_eq!(filter.search, [0xd3, 0xad, 0xbe, 0xef]);
+        let filter = filter.with_search_hex("D3AD").unwrap();
+        assert_eq!(filter.search, [0xd3, 0xad]);
+
+        assert!(filter.clone().with_search_hex("0xabc").is_err());
+        assert!(filter.clone().with_search_hex("0x").is_err());
+        assert!(filter.clone().with_search_hex("").is_err());
+        assert!(filter.with_search_hex("0xzz").is_err());
+    }
+
+    #[test]
+    fn extract_contracts() {
+        let db = create_test_rw_db();
+        let referenced = B256::with_last_byte(1);
+        let unreferenced = B256::with_last_byte(2);
+        // PUSH1 0x80, STOP
+        let code = Bytes::from_static(&[0x60, 0x80, 0x00]);
+        let account = Account { bytecode_hash: Some(referenced), ..Default::default() };
+
+        db.update(|tx| {
+            tx.put::<Bytecodes>(referenced, Bytecode::new_raw(code.clone()))?;
+            tx.put::<Bytecodes>(unreferenced, Bytecode::new_raw(code.clone()))?;
+            tx.put::<PlainAccountState>(Address::with_last_byte(1), account)
+        })
+        .unwrap()
+        .unwrap();
+
+        let tool = DbTool::new(&db, MAINNET.clone()).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(tool.extract_contracts(dir.path(), true, false).unwrap(), 1);
+        assert_eq!(std::fs::read(dir.path().join(format!("{referenced:?}.bin"))).unwrap(), code);
+        assert!(!dir.path().join(format!("{unreferenced:?}.bin")).exists());
+
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(tool.extract_contracts(dir.path(), false, true).unwrap(), 2);
+        for hash in [referenced, unreferenced] {
+            assert_eq!(
+                std::fs::read_to_string(dir.path().join(format!("{hash:?}.asm"))).unwrap(),
+                "0x0000: PUSH1 0x80\n0x0002: STOP\n"
+            );
+        }
+    }
+
+    #[test]
+    fn disassemble() {
+        // PUSH1 0x80, PUSH1 0x40, MSTORE, 0x0c (unassigned), truncated PUSH2
+        let code = [0x60, 0x80, 0x60, 0x40, 0x52, 0x0c, 0x61, 0x01];
+        assert_eq!(
+            disassemble_bytecode(&code),
+            "0x0000: PUSH1 0x80\n0x0002: PUSH1 0x40\n0x0004: MSTORE\n0x0005: INVALID(0x0c)\n0x0006: PUSH2 0x01\n"
+        );
+    }
+}